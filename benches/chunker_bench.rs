@@ -0,0 +1,127 @@
+//! Throughput harness for the hot path: `process_chunk`,
+//! `find_next_newline_simd`, `parse_temp`, and the full
+//! `process_file_parallel` pipeline.
+//!
+//! Uses `binggan` rather than `criterion` because it randomizes stack
+//! layout between iterations, which keeps L1/L2 alignment artifacts from
+//! biasing the SIMD newline scan against a scalar fallback. `binggan`
+//! reports MB/s from each input's byte size and persists a JSON baseline
+//! per bench under `target/`, so `cargo bench` also reports the delta
+//! against the previous run for regression tracking across commits.
+
+use binggan::{black_box, BenchRunner, InputGroup};
+use fast_1brc::{
+    find_next_newline_simd, parse_temp, process_chunk, process_file_parallel_with_config,
+    MalformedCounters, ValidationMode,
+};
+
+const STATION_COUNTS: [usize; 3] = [100, 1_000, 10_000];
+const ROWS_PER_FIXTURE: usize = 1_000_000;
+
+const THREAD_COUNTS: [usize; 3] = [1, 2, 4];
+/// Swept instead of the real 16 MiB `CHUNK_SIZE` so the same ~48 MB fixture
+/// also exercises up to ~190 chunks, not just the three a 16 MiB chunk
+/// would produce.
+const CHUNK_SIZES: [u64; 3] = [256 * 1024, 1024 * 1024, 4 * 1024 * 1024];
+/// Large enough to span several chunks even at the biggest size in
+/// `CHUNK_SIZES` above, so the sweep actually drives the chunk-boundary
+/// trim rather than completing in a single chunk.
+const LARGE_FIXTURE_ROWS: usize = 3_000_000;
+
+/// Builds a synthetic `measurements.txt`-shaped buffer with `station_count`
+/// distinct station names repeated until it holds `rows` lines.
+fn generate_fixture(station_count: usize, rows: usize) -> Vec<u8> {
+    let stations: Vec<String> = (0..station_count).map(|i| format!("Station{i}")).collect();
+    let mut buf = Vec::with_capacity(rows * 16);
+
+    for i in 0..rows {
+        let station = &stations[i % stations.len()];
+        let temp = ((i * 37) % 1000) as f64 / 10.0 - 50.0;
+        buf.extend_from_slice(station.as_bytes());
+        buf.push(b';');
+        buf.extend_from_slice(format!("{temp:.1}").as_bytes());
+        buf.push(b'\n');
+    }
+
+    buf
+}
+
+/// Compares `process_chunk`, `find_next_newline_simd`, and `parse_temp`
+/// over fixtures with a varying number of distinct stations, which is the
+/// dimension that stresses the `StationTable` load factor the hardest. No
+/// global-mutex merge runs here; `bench_process_file_parallel` covers the
+/// merge with a fixed 1,000 stations.
+fn bench_chunk_functions() {
+    let inputs: Vec<(String, Vec<u8>)> = STATION_COUNTS
+        .iter()
+        .map(|&n| (format!("{n}_stations"), generate_fixture(n, ROWS_PER_FIXTURE)))
+        .collect();
+
+    let mut group: InputGroup<Vec<u8>, u64> = InputGroup::new_with_inputs(inputs);
+    group.throughput(|data| data.len());
+
+    group.register("process_chunk", |data| {
+        let counters = MalformedCounters::default();
+        let table = process_chunk(black_box(data), 0, ValidationMode::Lenient, &counters).unwrap();
+        Some(table.iter().count() as u64)
+    });
+
+    group.register("find_next_newline_simd", |data| {
+        let mut start = 0;
+        let mut rows = 0u64;
+        while let Some(pos) = find_next_newline_simd(black_box(&data[start..])) {
+            start += pos + 1;
+            rows += 1;
+        }
+        Some(rows)
+    });
+
+    group.register("parse_temp", |data| {
+        let mut rows = 0u64;
+        for line in data.split(|&b| b == b'\n') {
+            if let Some(pos) = memchr::memchr(b';', line) {
+                if parse_temp(black_box(&line[pos + 1..])).is_some() {
+                    rows += 1;
+                }
+            }
+        }
+        Some(rows)
+    });
+
+    group.run();
+}
+
+/// Measures the full parallel pipeline end to end, sweeping both worker
+/// count and `CHUNK_SIZE` via `process_file_parallel_with_config` against a
+/// fixture large enough to span many chunks at every size swept.
+fn bench_process_file_parallel() {
+    let fixture_path = "bench_fixture_large.txt";
+    std::fs::write(fixture_path, generate_fixture(1_000, LARGE_FIXTURE_ROWS)).unwrap();
+    let file_size = std::fs::metadata(fixture_path).unwrap().len();
+
+    let mut runner = BenchRunner::with_name("process_file_parallel");
+    runner.set_input_size(file_size as usize);
+
+    for &num_threads in &THREAD_COUNTS {
+        for &chunk_size in &CHUNK_SIZES {
+            let label = format!("{num_threads}threads_{}kib_chunk", chunk_size / 1024);
+            runner.bench_function(label, move |_| {
+                let (map, _malformed) = process_file_parallel_with_config(
+                    black_box(fixture_path),
+                    ValidationMode::Lenient,
+                    num_threads,
+                    chunk_size,
+                )
+                .unwrap();
+                Some(map.len() as u64)
+            });
+        }
+    }
+
+    std::fs::remove_file(fixture_path).ok();
+}
+
+fn main() {
+    bench_chunk_functions();
+    bench_process_file_parallel();
+}
@@ -0,0 +1,785 @@
+#![feature(portable_simd)]
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io,
+    os::unix::fs::FileExt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crossbeam::thread;
+use fxhash::FxBuildHasher;
+use memchr::memrchr;
+use std::simd::prelude::SimdPartialEq;
+use std::simd::Simd;
+
+pub const CHUNK_SIZE: u64 = 16 * 1024 * 1024;
+// Worst-case 1BRC row: a 100-byte station name, `;`, a temperature up to
+// `-99.9` (5 bytes), and the trailing `\n` -> 107 bytes. Rounded up with
+// headroom so the leading-trim prefix search below always has the whole
+// straddling row in view, however long it is.
+pub const CHUNK_OVERLAP: u64 = 256;
+
+const STATION_TABLE_INITIAL_SLOTS: usize = 1024;
+const STATION_TABLE_INITIAL_ARENA: usize = STATION_TABLE_INITIAL_SLOTS * 16;
+const STATION_TABLE_MAX_LOAD_FACTOR: f64 = 0.7;
+
+use jemallocator::Jemalloc;
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+/// Aggregated station stats, kept as fixed-point tenths of a degree
+/// (e.g. a `min` of `-35` means `-3.5`) rather than `f64` so accumulation
+/// and merging are exact and identical regardless of merge order, and so
+/// the hot path never touches the FPU.
+#[derive(Debug, Clone, Copy)]
+pub struct Records {
+    pub count: u32,
+    pub min: i32,
+    pub max: i32,
+    pub sum: i64,
+}
+
+impl Records {
+    fn update(&mut self, temp_tenths: i32) {
+        self.count += 1;
+        self.sum += temp_tenths as i64;
+        if temp_tenths < self.min {
+            self.min = temp_tenths;
+        }
+        if temp_tenths > self.max {
+            self.max = temp_tenths;
+        }
+    }
+
+    /// The mean, in tenths, rounded to the nearest tenth with ties broken
+    /// away from zero (the challenge's HALF_UP rounding rule).
+    pub fn mean_tenths(&self) -> i64 {
+        round_half_away_from_zero(self.sum, self.count as i64)
+    }
+
+    fn merge(&mut self, other: &Records) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+}
+
+fn round_half_away_from_zero(numerator: i64, denominator: i64) -> i64 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if remainder.abs() * 2 >= denominator {
+        quotient + numerator.signum()
+    } else {
+        quotient
+    }
+}
+
+/// Formats fixed-point tenths (as produced by `parse_temp` and
+/// `Records::mean_tenths`) as a one-decimal string, e.g. `-35` -> `"-3.5"`.
+pub fn format_tenths(value: i64) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let abs = value.unsigned_abs();
+    format!("{sign}{}.{}", abs / 10, abs % 10)
+}
+
+/// A single slot in a `StationTable`'s open-addressing probe array.
+///
+/// An empty slot is represented by `key_len == u16::MAX`, since a real
+/// station name can never be that long.
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    key_offset: u32,
+    key_len: u16,
+    records: Records,
+}
+
+const EMPTY_KEY_LEN: u16 = u16::MAX;
+
+impl Slot {
+    const EMPTY: Slot = Slot {
+        key_offset: 0,
+        key_len: EMPTY_KEY_LEN,
+        records: Records {
+            count: 0,
+            min: i32::MAX,
+            max: i32::MIN,
+            sum: 0,
+        },
+    };
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.key_len == EMPTY_KEY_LEN
+    }
+}
+
+/// Arena-backed open-addressing hash table mapping station names to
+/// `Records`, used in place of a generic `HashMap` to avoid allocating a
+/// `String`/owned key per station.
+///
+/// Station name bytes are appended once to a contiguous `arena`; the
+/// probe array stores only an `(offset, len)` pair per slot alongside the
+/// aggregated `Records`, so a `StationTable` owns its keys outright and
+/// can outlive the read buffer they were parsed from.
+#[derive(Debug)]
+pub struct StationTable {
+    arena: Vec<u8>,
+    slots: Vec<Slot>,
+    mask: usize,
+    len: usize,
+}
+
+impl StationTable {
+    pub fn new() -> Self {
+        Self::with_capacity(STATION_TABLE_INITIAL_SLOTS)
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        let slot_count = capacity.next_power_of_two().max(16);
+        Self {
+            arena: Vec::with_capacity(STATION_TABLE_INITIAL_ARENA),
+            slots: vec![Slot::EMPTY; slot_count],
+            mask: slot_count - 1,
+            len: 0,
+        }
+    }
+
+    #[inline]
+    fn key_of(&self, slot: &Slot) -> &[u8] {
+        let start = slot.key_offset as usize;
+        let end = start + slot.key_len as usize;
+        &self.arena[start..end]
+    }
+
+    /// Finds the slot for `name`, inserting an empty `Records` accumulator
+    /// if this is the first time the station has been seen, and returns
+    /// its index in `self.slots`.
+    fn entry(&mut self, name: &[u8]) -> usize {
+        if (self.len + 1) as f64 > self.slots.len() as f64 * STATION_TABLE_MAX_LOAD_FACTOR {
+            self.grow();
+        }
+
+        let hash = fxhash::hash64(name);
+        let mut index = hash as usize & self.mask;
+
+        loop {
+            if self.slots[index].is_empty() {
+                let key_offset = self.arena.len() as u32;
+                self.arena.extend_from_slice(name);
+                self.slots[index] = Slot {
+                    key_offset,
+                    key_len: name.len() as u16,
+                    records: Slot::EMPTY.records,
+                };
+                self.len += 1;
+                return index;
+            }
+
+            if self.slots[index].key_len as usize == name.len()
+                && self.key_of(&self.slots[index]) == name
+            {
+                return index;
+            }
+
+            index = (index + 1) & self.mask;
+        }
+    }
+
+    #[inline]
+    fn update(&mut self, name: &[u8], temp_tenths: i32) {
+        let index = self.entry(name);
+        self.slots[index].records.update(temp_tenths);
+    }
+
+    /// Merges `other`'s station names and records into `self`, growing as
+    /// needed to keep the load factor bounded.
+    pub fn merge_from(&mut self, other: &StationTable) {
+        for slot in &other.slots {
+            if slot.is_empty() {
+                continue;
+            }
+            let name = other.key_of(slot);
+            let index = self.entry(name);
+            self.slots[index].records.merge(&slot.records);
+        }
+    }
+
+    fn grow(&mut self) {
+        let new_slot_count = self.slots.len() * 2;
+        let mut new_table = Self {
+            arena: Vec::with_capacity(self.arena.capacity() * 2),
+            slots: vec![Slot::EMPTY; new_slot_count],
+            mask: new_slot_count - 1,
+            len: 0,
+        };
+
+        for slot in &self.slots {
+            if slot.is_empty() {
+                continue;
+            }
+            let name = self.key_of(slot);
+            let index = new_table.entry(name);
+            new_table.slots[index].records = slot.records;
+        }
+
+        *self = new_table;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &Records)> {
+        self.slots
+            .iter()
+            .filter(|s| !s.is_empty())
+            .map(move |s| (self.key_of(s), &s.records))
+    }
+}
+
+impl Default for StationTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parses a 1BRC temperature (`-?\d{1,2}\.\d`) directly off its bytes into
+/// fixed-point tenths, e.g. `b"-3.5"` -> `-35`. Replaces the previous
+/// `from_utf8` + `trim` + `str::parse::<f64>` path, avoiding both the
+/// UTF-8 validation and the float parse on every row.
+pub fn parse_temp(bytes: &[u8]) -> Option<i32> {
+    let (negative, digits) = match bytes.split_first() {
+        Some((b'-', rest)) => (true, rest),
+        _ => (false, bytes),
+    };
+
+    let value = match digits {
+        [d0, b'.', d1] if d0.is_ascii_digit() && d1.is_ascii_digit() => {
+            (d0 - b'0') as i32 * 10 + (d1 - b'0') as i32
+        }
+        [d0, d1, b'.', d2] if d0.is_ascii_digit() && d1.is_ascii_digit() && d2.is_ascii_digit() => {
+            (d0 - b'0') as i32 * 100 + (d1 - b'0') as i32 * 10 + (d2 - b'0') as i32
+        }
+        _ => return None,
+    };
+
+    Some(if negative { -value } else { value })
+}
+
+/// How `process_chunk` should react to a record it cannot parse.
+///
+/// `Lenient` matches the historical behavior of silently dropping the
+/// line. `Count` keeps going but tallies malformed-line reasons in a
+/// `MalformedCounters` for a post-run summary. `Strict` aborts parsing
+/// and returns an `io::Error` pinpointing where the bad record starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationMode {
+    Lenient,
+    Count,
+    Strict,
+}
+
+/// Per-reason tallies of malformed records, accumulated per-thread during
+/// `Count` validation and merged into a single set of totals at the end of
+/// `process_file_parallel`.
+#[derive(Debug, Default)]
+pub struct MalformedCounters {
+    pub missing_delimiter: AtomicU64,
+    pub unparseable_temperature: AtomicU64,
+    pub invalid_utf8_station: AtomicU64,
+}
+
+impl MalformedCounters {
+    fn merge_into(&self, totals: &MalformedCounters) {
+        totals.missing_delimiter.fetch_add(
+            self.missing_delimiter.load(Ordering::Relaxed),
+            Ordering::Relaxed,
+        );
+        totals.unparseable_temperature.fetch_add(
+            self.unparseable_temperature.load(Ordering::Relaxed),
+            Ordering::Relaxed,
+        );
+        totals.invalid_utf8_station.fetch_add(
+            self.invalid_utf8_station.load(Ordering::Relaxed),
+            Ordering::Relaxed,
+        );
+    }
+
+    fn total(&self) -> u64 {
+        self.missing_delimiter.load(Ordering::Relaxed)
+            + self.unparseable_temperature.load(Ordering::Relaxed)
+            + self.invalid_utf8_station.load(Ordering::Relaxed)
+    }
+}
+
+#[inline]
+fn report_malformed(
+    mode: ValidationMode,
+    counter: &AtomicU64,
+    offset: u64,
+    reason: &str,
+) -> io::Result<()> {
+    match mode {
+        ValidationMode::Lenient => Ok(()),
+        ValidationMode::Count => {
+            counter.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+        ValidationMode::Strict => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("malformed record at byte offset {offset}: {reason}"),
+        )),
+    }
+}
+
+/// Parses `chunk` into a `StationTable`, applying `mode` to any line
+/// missing a `;` delimiter or carrying a temperature `parse_temp` can't
+/// make sense of. A non-UTF-8 station name is only checked (and reported
+/// via `mode`) outside `Lenient`; `Lenient` keeps the historical behavior
+/// of aggregating the raw bytes regardless, converted lossily at the very
+/// end in `finalize_table`. `read_start` is the absolute offset of
+/// `chunk[0]` in the original file, so `Strict` errors report real file
+/// positions; `Count` only tallies reasons and records no positions.
+pub fn process_chunk(
+    chunk: &[u8],
+    read_start: u64,
+    mode: ValidationMode,
+    counters: &MalformedCounters,
+) -> io::Result<StationTable> {
+    let mut table = StationTable::new();
+
+    let mut start = 0;
+    let len = chunk.len();
+
+    while start < len {
+        let end = match find_next_newline_simd(&chunk[start..]) {
+            Some(pos) => start + pos,
+            None => len,
+        };
+
+        let line = &chunk[start..end];
+        let line_offset = read_start + start as u64;
+
+        match memchr::memchr(b';', line) {
+            Some(pos) => {
+                let station = &line[..pos];
+                let temp_bytes = &line[pos + 1..];
+
+                if mode != ValidationMode::Lenient && std::str::from_utf8(station).is_err() {
+                    report_malformed(
+                        mode,
+                        &counters.invalid_utf8_station,
+                        line_offset,
+                        "invalid UTF-8 in station name",
+                    )?;
+                } else if let Some(temp) = parse_temp(temp_bytes) {
+                    table.update(station, temp);
+                } else {
+                    report_malformed(
+                        mode,
+                        &counters.unparseable_temperature,
+                        line_offset,
+                        "unparseable temperature",
+                    )?;
+                }
+            }
+            None => {
+                report_malformed(
+                    mode,
+                    &counters.missing_delimiter,
+                    line_offset,
+                    "missing ';' delimiter",
+                )?;
+            }
+        }
+
+        start = end + 1;
+    }
+
+    Ok(table)
+}
+
+pub fn find_next_newline_simd(buffer: &[u8]) -> Option<usize> {
+    let mut index = 0;
+    let simd_size = 64;
+
+    while index + simd_size <= buffer.len() {
+        let bytes = Simd::<u8, 64>::from_slice(&buffer[index..index + simd_size]);
+        let mask = bytes.simd_eq(Simd::splat(b'\n'));
+        let bits = mask.to_bitmask();
+
+        if bits != 0 {
+            let pos = bits.trailing_zeros() as usize;
+            return Some(index + pos);
+        }
+
+        index += simd_size;
+    }
+
+    (index..buffer.len()).find(|&i| buffer[i] == b'\n')
+}
+
+/// Supplies the bytes for a `[offset, offset + len)` byte range of the
+/// input file to the chunk-dispatch loop shared by the read-based and
+/// mmap-backed drivers below. The read-based source copies into `scratch`;
+/// the mmap source already has the whole file resident and ignores it.
+trait ChunkSource: Sync {
+    fn read<'a>(&'a self, offset: u64, len: u64, scratch: &'a mut [u8]) -> io::Result<&'a [u8]>;
+}
+
+struct FileChunkSource<'a> {
+    file: &'a File,
+}
+
+impl ChunkSource for FileChunkSource<'_> {
+    fn read<'a>(&'a self, offset: u64, len: u64, scratch: &'a mut [u8]) -> io::Result<&'a [u8]> {
+        let buffer = &mut scratch[..len as usize];
+        self.file.read_exact_at(buffer, offset)?;
+        Ok(buffer)
+    }
+}
+
+#[cfg(feature = "mmap")]
+struct MmapChunkSource<'a> {
+    mmap: &'a memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl ChunkSource for MmapChunkSource<'_> {
+    fn read<'a>(&'a self, offset: u64, len: u64, _scratch: &'a mut [u8]) -> io::Result<&'a [u8]> {
+        Ok(&self.mmap[offset as usize..(offset + len) as usize])
+    }
+}
+
+/// Chunk-dispatch loop shared by `process_file_parallel` and
+/// `process_file_parallel_mmap`: a pool of `num_threads` workers pulls
+/// disjoint `chunk_size` byte ranges off a shared atomic offset, trims each
+/// to whole lines, parses it into a `StationTable`, and merges into a
+/// global table. `source` is the only thing that differs between the two
+/// callers; `num_threads`/`chunk_size` are exposed as parameters (rather
+/// than hardcoded to `num_cpus::get()`/`CHUNK_SIZE`) so benches can sweep
+/// them.
+fn dispatch_chunks<S: ChunkSource>(
+    source: &S,
+    file_size: u64,
+    mode: ValidationMode,
+    num_threads: usize,
+    chunk_size: u64,
+    scratch_len: usize,
+) -> io::Result<(HashMap<String, Records, FxBuildHasher>, MalformedCounters)> {
+    let offset = AtomicU64::new(0);
+
+    let global_table = Arc::new(Mutex::new(StationTable::new()));
+    let totals = MalformedCounters::default();
+    // Chunks are processed concurrently and out of order, so the Strict
+    // error that happens to land first in wall-clock time isn't necessarily
+    // the one at the lowest file offset. Every chunk's error is recorded
+    // here with its offset, and the minimum-offset one is reported once all
+    // workers have finished.
+    let errors: Mutex<Vec<(u64, io::Error)>> = Mutex::new(Vec::new());
+
+    thread::scope(|s| {
+        for _ in 0..num_threads {
+            let global_table = Arc::clone(&global_table);
+            let offset = &offset;
+            let totals = &totals;
+            let errors = &errors;
+
+            s.spawn(move |_| {
+                let mut scratch = vec![0u8; scratch_len];
+                let counters = MalformedCounters::default();
+
+                loop {
+                    let chunk_start = offset.fetch_add(chunk_size, Ordering::SeqCst);
+                    if chunk_start >= file_size {
+                        break;
+                    }
+
+                    let read_start = if chunk_start == 0 {
+                        0
+                    } else {
+                        chunk_start - CHUNK_OVERLAP
+                    };
+                    // Every chunk's window ends exactly at its own nominal
+                    // boundary (never past it, not even the first chunk,
+                    // which has no leading overlap to borrow from). That way
+                    // the tail trim below always cuts at the same place the
+                    // next chunk's leading trim expects to find it.
+                    let nominal_end = std::cmp::min(chunk_start + chunk_size, file_size);
+                    let read_size = nominal_end - read_start;
+
+                    let buffer = match source.read(read_start, read_size, &mut scratch) {
+                        Ok(buffer) => buffer,
+                        Err(e) => {
+                            eprintln!("Error reading file at position {}: {}", read_start, e);
+                            break;
+                        }
+                    };
+
+                    let mut chunk = buffer;
+                    let mut chunk_abs_start = read_start;
+                    if chunk_start != 0 {
+                        // The previous chunk's tail trim (below) cuts at the
+                        // last newline in its own window, which ends at this
+                        // chunk's `chunk_start` — i.e. the same cut point is
+                        // the last newline within our leading `CHUNK_OVERLAP`
+                        // prefix. Search only that prefix, and take the last
+                        // (not first) match, so we resume at exactly that cut
+                        // rather than at some earlier row the previous chunk
+                        // already counted.
+                        let overlap_prefix_len = std::cmp::min(CHUNK_OVERLAP as usize, chunk.len());
+                        match memrchr(b'\n', &chunk[..overlap_prefix_len]) {
+                            Some(pos) => {
+                                chunk = &chunk[pos + 1..];
+                                chunk_abs_start = read_start + pos as u64 + 1;
+                            }
+                            None => continue,
+                        }
+                    }
+
+                    if let Some(pos) = memrchr(b'\n', chunk) {
+                        chunk = &chunk[..pos];
+                    }
+
+                    match process_chunk(chunk, chunk_abs_start, mode, &counters) {
+                        Ok(local_table) => {
+                            let mut global_table = global_table.lock().unwrap();
+                            global_table.merge_from(&local_table);
+                        }
+                        Err(e) => {
+                            errors.lock().unwrap().push((chunk_abs_start, e));
+                            break;
+                        }
+                    }
+                }
+
+                counters.merge_into(totals);
+            });
+        }
+    })
+    .map_err(|_| io::Error::other("Thread error"))?;
+
+    if let Some((_, e)) = errors
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .min_by_key(|(offset, _)| *offset)
+    {
+        return Err(e);
+    }
+
+    if mode == ValidationMode::Count && totals.total() > 0 {
+        eprintln!(
+            "malformed records: {} missing delimiter, {} unparseable temperature, {} invalid UTF-8 station name",
+            totals.missing_delimiter.load(Ordering::Relaxed),
+            totals.unparseable_temperature.load(Ordering::Relaxed),
+            totals.invalid_utf8_station.load(Ordering::Relaxed),
+        );
+    }
+
+    let global_table = Arc::try_unwrap(global_table)
+        .expect("More than one Arc pointer")
+        .into_inner()
+        .unwrap();
+
+    Ok((finalize_table(global_table), totals))
+}
+
+pub fn process_file_parallel(
+    filename: &str,
+    mode: ValidationMode,
+) -> io::Result<(HashMap<String, Records, FxBuildHasher>, MalformedCounters)> {
+    process_file_parallel_with_config(filename, mode, num_cpus::get(), CHUNK_SIZE)
+}
+
+/// Same as `process_file_parallel`, but with `num_threads`/`chunk_size`
+/// exposed instead of defaulting to `num_cpus::get()`/`CHUNK_SIZE`, so a
+/// bench harness can sweep them.
+pub fn process_file_parallel_with_config(
+    filename: &str,
+    mode: ValidationMode,
+    num_threads: usize,
+    chunk_size: u64,
+) -> io::Result<(HashMap<String, Records, FxBuildHasher>, MalformedCounters)> {
+    let file = File::open(filename)?;
+    let file_size = file.metadata()?.len();
+    let source = FileChunkSource { file: &file };
+
+    dispatch_chunks(
+        &source,
+        file_size,
+        mode,
+        num_threads,
+        chunk_size,
+        (chunk_size + CHUNK_OVERLAP) as usize,
+    )
+}
+
+/// mmap-backed counterpart to `process_file_parallel`: instead of giving
+/// each worker a private `vec![0u8; ...]` read buffer and issuing a
+/// `read_exact_at` per chunk, the whole file is mapped once and workers
+/// slice directly into the mapping, so there is no per-chunk copy and no
+/// re-read of the `CHUNK_OVERLAP` boundary bytes.
+#[cfg(feature = "mmap")]
+pub fn process_file_parallel_mmap(
+    filename: &str,
+    mode: ValidationMode,
+) -> io::Result<(HashMap<String, Records, FxBuildHasher>, MalformedCounters)> {
+    process_file_parallel_mmap_with_config(filename, mode, num_cpus::get(), CHUNK_SIZE)
+}
+
+/// Same as `process_file_parallel_mmap`, but with `num_threads`/`chunk_size`
+/// exposed instead of defaulting to `num_cpus::get()`/`CHUNK_SIZE`, so a
+/// bench harness can sweep them.
+#[cfg(feature = "mmap")]
+pub fn process_file_parallel_mmap_with_config(
+    filename: &str,
+    mode: ValidationMode,
+    num_threads: usize,
+    chunk_size: u64,
+) -> io::Result<(HashMap<String, Records, FxBuildHasher>, MalformedCounters)> {
+    let file = File::open(filename)?;
+    let file_size = file.metadata()?.len();
+
+    let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    let _ = mmap.advise(memmap2::Advice::Sequential);
+    let _ = mmap.advise(memmap2::Advice::WillNeed);
+    let source = MmapChunkSource { mmap: &mmap };
+
+    // The mmap source never touches the scratch buffer, so it doesn't need
+    // one sized for a whole chunk.
+    dispatch_chunks(&source, file_size, mode, num_threads, chunk_size, 0)
+}
+
+pub fn finalize_table(table: StationTable) -> HashMap<String, Records, FxBuildHasher> {
+    let mut global_map = HashMap::with_hasher(FxBuildHasher::default());
+    for (station_bytes, records) in table.iter() {
+        let station = String::from_utf8_lossy(station_bytes).to_string();
+        global_map.insert(station, *records);
+    }
+    global_map
+}
+
+#[cfg(feature = "mmap")]
+pub fn process_file(
+    filename: &str,
+    mode: ValidationMode,
+) -> io::Result<(HashMap<String, Records, FxBuildHasher>, MalformedCounters)> {
+    process_file_parallel_mmap(filename, mode)
+}
+
+#[cfg(not(feature = "mmap"))]
+pub fn process_file(
+    filename: &str,
+    mode: ValidationMode,
+) -> io::Result<(HashMap<String, Records, FxBuildHasher>, MalformedCounters)> {
+    process_file_parallel(filename, mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Regression test for a bug where the leading-boundary trim searched the
+    // whole read buffer for the last newline instead of the last one within
+    // the `CHUNK_OVERLAP` prefix, which silently sliced away nearly all of
+    // every chunk after the first. The fixture must exceed a couple multiples
+    // of `CHUNK_SIZE` to actually exercise more than one chunk.
+    #[test]
+    fn process_file_parallel_spans_multiple_chunks() {
+        let row: &[u8] = b"Test;12.3\n";
+        let target_bytes = (CHUNK_SIZE * 5 / 2) as usize;
+        let rows = target_bytes / row.len();
+
+        let mut buf = Vec::with_capacity(rows * row.len());
+        for _ in 0..rows {
+            buf.extend_from_slice(row);
+        }
+
+        let path = std::env::temp_dir().join(format!(
+            "fast_1brc_multi_chunk_test_{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, &buf).unwrap();
+
+        let result = process_file_parallel(path.to_str().unwrap(), ValidationMode::Strict);
+        std::fs::remove_file(&path).ok();
+
+        let (stats, malformed) = result.unwrap();
+        assert_eq!(malformed.total(), 0);
+        assert_eq!(stats["Test"].count as usize, rows);
+    }
+
+    // Regression test for a bug where `CHUNK_OVERLAP` (64 bytes) was
+    // narrower than the longest possible 1BRC row (a 100-byte station name
+    // plus delimiter, temperature, and newline, ~107 bytes). A straddling
+    // row longer than `CHUNK_OVERLAP` put the previous chunk's real
+    // cut-newline before `read_start`, so the leading trim found nothing
+    // and silently dropped the whole chunk via `None => continue`.
+    #[test]
+    fn process_file_parallel_handles_station_name_longer_than_old_overlap() {
+        let filler: &[u8] = b"Test;12.3\n";
+        let chunk_size: u64 = 4096;
+        let lead_in_rows = (chunk_size as usize - 80) / filler.len();
+
+        let mut buf = Vec::new();
+        for _ in 0..lead_in_rows {
+            buf.extend_from_slice(filler);
+        }
+
+        let long_station = "A".repeat(85);
+        let long_row = format!("{long_station};12.3\n");
+        buf.extend_from_slice(long_row.as_bytes());
+
+        for _ in 0..lead_in_rows {
+            buf.extend_from_slice(filler);
+        }
+
+        let path = std::env::temp_dir().join(format!(
+            "fast_1brc_long_station_test_{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, &buf).unwrap();
+
+        let result = process_file_parallel_with_config(
+            path.to_str().unwrap(),
+            ValidationMode::Strict,
+            2,
+            chunk_size,
+        );
+        std::fs::remove_file(&path).ok();
+
+        let (stats, malformed) = result.unwrap();
+        assert_eq!(malformed.total(), 0);
+        assert_eq!(stats["Test"].count as usize, lead_in_rows * 2);
+        assert_eq!(stats[long_station.as_str()].count, 1);
+    }
+
+    // Regression test: `Lenient` used to drop any row whose station name
+    // wasn't valid UTF-8, but the pre-validation-mode baseline never
+    // checked UTF-8 at all and aggregated such rows on their raw bytes
+    // (converting lossily only at the very end in `finalize_table`).
+    // `Lenient` must still behave that way.
+    #[test]
+    fn process_chunk_lenient_keeps_invalid_utf8_station_raw() {
+        let mut chunk = Vec::new();
+        chunk.extend_from_slice(b"Good;1.0\n");
+        chunk.extend_from_slice(&[0xFF, b';', b'2', b'.', b'0', b'\n']);
+        chunk.extend_from_slice(b"Good;3.0\n");
+
+        let counters = MalformedCounters::default();
+        let table = process_chunk(&chunk, 0, ValidationMode::Lenient, &counters).unwrap();
+
+        assert_eq!(counters.total(), 0);
+        assert_eq!(table.iter().count(), 2);
+
+        let good = table.iter().find(|(name, _)| *name == b"Good").unwrap().1;
+        assert_eq!(good.count, 2);
+
+        let invalid = table.iter().find(|(name, _)| *name == [0xFF]).unwrap().1;
+        assert_eq!(invalid.count, 1);
+    }
+}